@@ -0,0 +1,21 @@
+use soroban_sdk::contracterror;
+
+#[contracterror]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum SwapError {
+    EscrowNotFound = 1,
+    NotActive = 2,
+    InvalidSecret = 3,
+    Unauthorized = 4,
+    PartialFillsNotSupported = 5,
+    InvalidMerkleProof = 6,
+    FillOutOfOrder = 7,
+    FillExceedsAmount = 8,
+    /// The active `Phase` doesn't permit this action (e.g. withdrawing during
+    /// `Finality`, or cancelling during a withdrawal phase).
+    WrongPhase = 9,
+    /// Another escrow with identical `Immutables` (and thus the same derived id)
+    /// is already stored; creating this one would silently overwrite it.
+    EscrowAlreadyExists = 10,
+}
@@ -1,36 +1,135 @@
-use soroban_sdk::{Env, String};
+use soroban_sdk::{BytesN, Env, String, ToXdr};
 use crate::types::{Immutables, EscrowState, DataKey};
+use crate::errors::SwapError;
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// Extend a persistent entry's TTL once it has fewer than this many ledgers left...
+const ESCROW_TTL_THRESHOLD: u32 = 17280; // ~1 day of ledgers (5s close time)
+/// ...out to this many ledgers from now (~30 days).
+const ESCROW_TTL_EXTEND_TO: u32 = 518400;
+
+/// Persistence boundary for escrow data, kept separate from `SwapContract` so the
+/// business logic in `EscrowManager` is parametric over the storage backend: the
+/// real implementation below targets Soroban persistent storage, but a fake can be
+/// substituted in tests without touching contract storage at all.
+pub trait EscrowStore {
+    fn get_escrow(&self, id: &String) -> Option<Immutables>;
+    fn set_escrow(&self, id: &String, immutables: &Immutables);
+    fn get_state(&self, id: &String) -> Option<EscrowState>;
+    fn set_state(&self, id: &String, state: &EscrowState);
+    /// Cumulative amount released so far for a partially-filled escrow; `0` if
+    /// nothing has been recorded yet.
+    fn get_filled(&self, id: &String) -> i128;
+    fn set_filled(&self, id: &String, amount: i128);
+    /// Push the entry's expiration ledger back out, since Soroban persistent
+    /// entries otherwise expire out from under a long-lived escrow.
+    fn bump_ttl(&self, id: &String);
+}
+
+impl EscrowStore for Env {
+    fn get_escrow(&self, id: &String) -> Option<Immutables> {
+        self.storage().persistent().get(&DataKey::Escrow(id.clone()))
+    }
+
+    fn set_escrow(&self, id: &String, immutables: &Immutables) {
+        self.storage().persistent().set(&DataKey::Escrow(id.clone()), immutables);
+    }
+
+    fn get_state(&self, id: &String) -> Option<EscrowState> {
+        self.storage().persistent().get(&DataKey::EscrowState(id.clone()))
+    }
+
+    fn set_state(&self, id: &String, state: &EscrowState) {
+        self.storage().persistent().set(&DataKey::EscrowState(id.clone()), state);
+    }
+
+    fn get_filled(&self, id: &String) -> i128 {
+        self.storage().persistent()
+            .get(&DataKey::EscrowFilled(id.clone()))
+            .unwrap_or(0)
+    }
+
+    fn set_filled(&self, id: &String, amount: i128) {
+        self.storage().persistent().set(&DataKey::EscrowFilled(id.clone()), &amount);
+    }
+
+    fn bump_ttl(&self, id: &String) {
+        self.storage().persistent().extend_ttl(
+            &DataKey::Escrow(id.clone()),
+            ESCROW_TTL_THRESHOLD,
+            ESCROW_TTL_EXTEND_TO,
+        );
+        self.storage().persistent().extend_ttl(
+            &DataKey::EscrowState(id.clone()),
+            ESCROW_TTL_THRESHOLD,
+            ESCROW_TTL_EXTEND_TO,
+        );
+        let filled_key = DataKey::EscrowFilled(id.clone());
+        if self.storage().persistent().has(&filled_key) {
+            self.storage().persistent().extend_ttl(
+                &filled_key,
+                ESCROW_TTL_THRESHOLD,
+                ESCROW_TTL_EXTEND_TO,
+            );
+        }
+    }
+}
 
 pub struct EscrowManager;
 
 impl EscrowManager {
-    pub fn create(
-        env: &Env,
-        immutables: &Immutables,
-    ) -> String {
+    pub fn create(env: &Env, store: &impl EscrowStore, immutables: &Immutables) -> Result<String, SwapError> {
         let escrow_id = Self::generate_id(env, immutables);
-        
-        // Store escrow data
-        env.storage().persistent().set(&DataKey::Escrow(escrow_id.clone()), immutables);
-        env.storage().persistent().set(&DataKey::EscrowState(escrow_id.clone()), &EscrowState::Active);
-        
-        escrow_id
-    }
-    
-    pub fn get(env: &Env, escrow_id: &String) -> Option<Immutables> {
-        env.storage().persistent().get(&DataKey::Escrow(escrow_id.clone()))
-    }
-    
-    pub fn get_state(env: &Env, escrow_id: &String) -> Option<EscrowState> {
-        env.storage().persistent().get(&DataKey::EscrowState(escrow_id.clone()))
-    }
-    
-    pub fn set_state(env: &Env, escrow_id: &String, state: &EscrowState) {
-        env.storage().persistent().set(&DataKey::EscrowState(escrow_id.clone()), state);
-    }
-    
-    fn generate_id(env: &Env, _immutables: &Immutables) -> String {
-        // Simple ID generation using timestamp
-        String::from_str(env, "escrow_id")
-    }
-}
\ No newline at end of file
+
+        if store.get_escrow(&escrow_id).is_some() {
+            return Err(SwapError::EscrowAlreadyExists);
+        }
+
+        store.set_escrow(&escrow_id, immutables);
+        store.set_state(&escrow_id, &EscrowState::Active);
+        store.bump_ttl(&escrow_id);
+
+        Ok(escrow_id)
+    }
+
+    pub fn get(store: &impl EscrowStore, escrow_id: &String) -> Option<Immutables> {
+        store.get_escrow(escrow_id)
+    }
+
+    pub fn get_state(store: &impl EscrowStore, escrow_id: &String) -> Option<EscrowState> {
+        store.get_state(escrow_id)
+    }
+
+    pub fn set_state(store: &impl EscrowStore, escrow_id: &String, state: &EscrowState) {
+        store.set_state(escrow_id, state);
+        store.bump_ttl(escrow_id);
+    }
+
+    pub fn get_filled(store: &impl EscrowStore, escrow_id: &String) -> i128 {
+        store.get_filled(escrow_id)
+    }
+
+    pub fn set_filled(store: &impl EscrowStore, escrow_id: &String, amount: i128) {
+        store.set_filled(escrow_id, amount);
+        store.bump_ttl(escrow_id);
+    }
+
+    /// Derive the escrow id as sha256 of the full `Immutables` (including
+    /// `chain_id`), so every distinct escrow gets a unique, independently
+    /// recomputable id and two escrows can never collide in storage.
+    fn generate_id(env: &Env, immutables: &Immutables) -> String {
+        let preimage = immutables.clone().to_xdr(env);
+        let digest: BytesN<32> = env.crypto().sha256(&preimage).to_bytes();
+        hex_string(env, &digest)
+    }
+}
+
+fn hex_string(env: &Env, digest: &BytesN<32>) -> String {
+    let mut buf = [0u8; 64];
+    for (i, byte) in digest.to_array().iter().enumerate() {
+        buf[i * 2] = HEX_DIGITS[(byte >> 4) as usize];
+        buf[i * 2 + 1] = HEX_DIGITS[(byte & 0x0f) as usize];
+    }
+    String::from_str(env, core::str::from_utf8(&buf).unwrap())
+}
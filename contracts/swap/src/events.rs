@@ -1,4 +1,4 @@
-use soroban_sdk::{contracttype, Address, String};
+use soroban_sdk::{contracttype, Address, Bytes, String};
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -16,7 +16,7 @@ pub struct EscrowWithdrawn {
     pub escrow_id: String,
     pub taker: Address,
     pub amount: i128,
-    pub secret: String,
+    pub secret: Bytes,
 }
 
 #[contracttype]
@@ -25,4 +25,13 @@ pub struct EscrowCancelled {
     pub escrow_id: String,
     pub maker: Address,
     pub amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowPartiallyWithdrawn {
+    pub escrow_id: String,
+    pub taker: Address,
+    pub fill_amount: i128,
+    pub secret_index: u32,
 }
\ No newline at end of file
@@ -1,21 +1,30 @@
-use soroban_sdk::{Env, String};
+use soroban_sdk::{Bytes, BytesN, Env};
 
 pub struct HashlockValidator {
-    expected_hash: String,
+    expected_hash: BytesN<32>,
+    chain_id: u32,
 }
 
 impl HashlockValidator {
-    pub fn new(expected_hash: String) -> Self {
-        Self { expected_hash }
+    pub fn new(expected_hash: BytesN<32>, chain_id: u32) -> Self {
+        Self { expected_hash, chain_id }
     }
-    
-    pub fn validate(&self, _env: &Env, secret: &String) -> bool {
-        // Simple comparison for now - in production you'd want proper hashing
-        secret == &self.expected_hash
+
+    pub fn validate(&self, env: &Env, secret: &Bytes) -> bool {
+        hash_secret(env, secret, self.chain_id) == self.expected_hash
     }
 }
 
-pub fn create_hashlock(_env: &Env, secret: &String) -> String {
-    // Simple return secret for now - in production you'd want proper hashing
-    secret.clone()
-}
\ No newline at end of file
+/// Hash a secret preimage, bound to `chain_id`, into the 32-byte digest stored as
+/// `Immutables.hashlock`. Binding the chain id here means a secret revealed to
+/// unlock an escrow on one network cannot be replayed against an identically
+/// parameterized escrow on another.
+pub fn create_hashlock(env: &Env, secret: &Bytes, chain_id: u32) -> BytesN<32> {
+    hash_secret(env, secret, chain_id)
+}
+
+fn hash_secret(env: &Env, secret: &Bytes, chain_id: u32) -> BytesN<32> {
+    let mut preimage = secret.clone();
+    preimage.append(&Bytes::from_array(env, &chain_id.to_be_bytes()));
+    env.crypto().sha256(&preimage).to_bytes()
+}
@@ -2,21 +2,27 @@
 
 use soroban_sdk::{
     contract, contractimpl, contractmeta,
-    token, Address, Env, String, Symbol, Vec, Bytes,
+    token, Address, Bytes, BytesN, Env, String, Symbol, Vec,
 };
 
 pub mod types;
 pub mod events;
+pub mod errors;
 pub mod timelock;
 pub mod hashlock;
 pub mod escrow;
+pub mod merkle;
 
 #[cfg(test)]
 mod test;
 
 pub use types::*;
 pub use events::*;
+pub use errors::*;
 pub use escrow::*;
+pub use timelock::*;
+
+use hashlock::create_hashlock;
 
 contractmeta!(
     key = "Description",
@@ -28,147 +34,244 @@ pub struct SwapContract;
 
 #[contractimpl]
 impl SwapContract {
-    /// Initialize a new escrow for atomic swap
+    /// Initialize a new escrow for atomic swap. `taker` posts `safety_deposit`
+    /// alongside the swap `amount`, to be paid out to whoever triggers the
+    /// completing withdrawal or cancellation once the public phase opens.
     pub fn create_escrow(
         env: Env,
         maker: Address,
         taker: Address,
         token: Address,
         amount: i128,
-        hashlock: String,
-        timelock_start: u64,
-        timelock_end: u64,
-    ) -> String {
+        hashlock: BytesN<32>,
+        finality_duration: u64,
+        private_withdraw_duration: u64,
+        public_withdraw_duration: u64,
+        private_cancel_duration: u64,
+        chain_id: u32,
+        parts: u32,
+        safety_deposit: i128,
+    ) -> Result<String, SwapError> {
         maker.require_auth();
-        
-        let escrow_id = generate_escrow_id(&env, &maker, &taker, &token, amount);
-        
+        taker.require_auth();
+
+        let timelock = Timelock::new(
+            env.ledger().timestamp(),
+            finality_duration,
+            private_withdraw_duration,
+            public_withdraw_duration,
+            private_cancel_duration,
+        );
+
         let immutables = Immutables {
             maker: maker.clone(),
             taker: taker.clone(),
             token: token.clone(),
             amount,
-            hashlock: hashlock.clone(),
-            timelock_start,
-            timelock_end,
+            hashlock,
+            timelock,
+            chain_id,
+            parts,
+            safety_deposit,
         };
-        
-        // Store escrow data
-        env.storage().persistent().set(&DataKey::Escrow(escrow_id.clone()), &immutables);
-        env.storage().persistent().set(&DataKey::EscrowState(escrow_id.clone()), &EscrowState::Active);
-        
-        // Transfer tokens to contract
+
+        let escrow_id = EscrowManager::create(&env, &env, &immutables)?;
+
+        // Transfer swap amount and safety deposit into the contract
         let token_client = token::Client::new(&env, &token);
         token_client.transfer(&maker, &env.current_contract_address(), &amount);
-        
+        if safety_deposit > 0 {
+            token_client.transfer(&taker, &env.current_contract_address(), &safety_deposit);
+        }
+
         // Emit event
         env.events().publish(
             (Symbol::new(&env, "escrow_created"),),
             (escrow_id.clone(), maker, taker, token, amount)
         );
-        
-        escrow_id
+
+        Ok(escrow_id)
     }
-    
-    /// Withdraw from escrow using secret
-    pub fn withdraw(env: Env, escrow_id: String, secret: String) -> bool {
-        let immutables: Immutables = env.storage().persistent()
-            .get(&DataKey::Escrow(escrow_id.clone()))
-            .unwrap();
-        
-        let state: EscrowState = env.storage().persistent()
-            .get(&DataKey::EscrowState(escrow_id.clone()))
-            .unwrap();
-        
-        // Verify escrow is active
+
+    /// Withdraw from escrow using secret. In `PrivateWithdraw` only `taker` may
+    /// call; in `PublicWithdraw` anyone may, collecting the safety deposit.
+    pub fn withdraw(env: Env, escrow_id: String, secret: Bytes, caller: Address) -> Result<bool, SwapError> {
+        caller.require_auth();
+
+        let immutables = EscrowManager::get(&env, &escrow_id).ok_or(SwapError::EscrowNotFound)?;
+        let state = EscrowManager::get_state(&env, &escrow_id).ok_or(SwapError::EscrowNotFound)?;
+
         if state != EscrowState::Active {
-            panic!("Escrow not active");
+            return Err(SwapError::NotActive);
         }
-        
-        // Verify timelock
-        let current_time = env.ledger().timestamp();
-        if current_time < immutables.timelock_start || current_time > immutables.timelock_end {
-            panic!("Outside timelock window");
+
+        match immutables.timelock.phase_at(&env) {
+            Phase::PrivateWithdraw if caller != immutables.taker => return Err(SwapError::Unauthorized),
+            Phase::PrivateWithdraw | Phase::PublicWithdraw => {}
+            Phase::Finality | Phase::PrivateCancel | Phase::PublicCancel => return Err(SwapError::WrongPhase),
         }
-        
+
         // Verify hashlock
-        if !verify_hashlock(&env, &immutables.hashlock, &secret) {
-            panic!("Invalid secret");
+        if !verify_hashlock(&env, &immutables.hashlock, &secret, immutables.chain_id) {
+            return Err(SwapError::InvalidSecret);
         }
-        
-        // Transfer tokens to taker
+
+        // Transfer tokens to taker and the safety deposit to whoever resolved it
         let token_client = token::Client::new(&env, &immutables.token);
         token_client.transfer(&env.current_contract_address(), &immutables.taker, &immutables.amount);
-        
+        pay_safety_deposit(&env, &immutables, &caller);
+
         // Update state
-        env.storage().persistent().set(&DataKey::EscrowState(escrow_id.clone()), &EscrowState::Withdrawn);
-        
+        EscrowManager::set_state(&env, &escrow_id, &EscrowState::Withdrawn);
+
         // Emit event
         env.events().publish(
             (Symbol::new(&env, "escrow_withdrawn"),),
             (escrow_id, immutables.taker, immutables.amount, secret)
         );
-        
-        true
+
+        Ok(true)
     }
-    
-    /// Cancel escrow and return funds to maker
-    pub fn cancel(env: Env, escrow_id: String) -> bool {
-        let immutables: Immutables = env.storage().persistent()
-            .get(&DataKey::Escrow(escrow_id.clone()))
-            .unwrap();
-        
-        let state: EscrowState = env.storage().persistent()
-            .get(&DataKey::EscrowState(escrow_id.clone()))
-            .unwrap();
-        
-        // Verify escrow is active
+
+    /// Claim a cumulative fraction of a partial-fill escrow by revealing the
+    /// secret for `secret_index` and its Merkle proof against `Immutables.hashlock`.
+    /// Subject to the same `PrivateWithdraw`/`PublicWithdraw` phase rules as
+    /// `withdraw`; the safety deposit is paid out on the call that completes
+    /// the final segment.
+    pub fn withdraw_partial(
+        env: Env,
+        escrow_id: String,
+        fill_amount: i128,
+        secret_index: u32,
+        secret: Bytes,
+        proof: Vec<BytesN<32>>,
+        caller: Address,
+    ) -> Result<bool, SwapError> {
+        caller.require_auth();
+
+        let immutables = EscrowManager::get(&env, &escrow_id).ok_or(SwapError::EscrowNotFound)?;
+        let state = EscrowManager::get_state(&env, &escrow_id).ok_or(SwapError::EscrowNotFound)?;
+
+        if immutables.parts < 2 {
+            return Err(SwapError::PartialFillsNotSupported);
+        }
         if state != EscrowState::Active {
-            panic!("Escrow not active");
+            return Err(SwapError::NotActive);
         }
-        
-        // Verify timelock has expired
-        let current_time = env.ledger().timestamp();
-        if current_time <= immutables.timelock_end {
-            panic!("Timelock not expired");
+
+        match immutables.timelock.phase_at(&env) {
+            Phase::PrivateWithdraw if caller != immutables.taker => return Err(SwapError::Unauthorized),
+            Phase::PrivateWithdraw | Phase::PublicWithdraw => {}
+            Phase::Finality | Phase::PrivateCancel | Phase::PublicCancel => return Err(SwapError::WrongPhase),
+        }
+
+        if secret_index > immutables.parts {
+            return Err(SwapError::InvalidMerkleProof);
+        }
+
+        // Verify the secret unlocks this index of the Merkle tree of secrets
+        let leaf = merkle::leaf_hash(&env, secret_index, &secret);
+        if !merkle::verify_proof(&env, leaf, &proof, &immutables.hashlock) {
+            return Err(SwapError::InvalidMerkleProof);
+        }
+
+        // `secret_index` must cross the next unfilled segment boundary: this
+        // rejects replaying an already-used index and going back to a lower one,
+        // since boundaries are strictly increasing in index. A higher index may
+        // be claimed directly, releasing several segments in one call.
+        let filled_amount = EscrowManager::get_filled(&env, &escrow_id);
+        let boundary = immutables.amount * secret_index as i128 / immutables.parts as i128;
+        if boundary <= filled_amount {
+            return Err(SwapError::FillOutOfOrder);
         }
-        
-        // Only maker can cancel
-        immutables.maker.require_auth();
-        
-        // Transfer tokens back to maker
+
+        let expected_fill = boundary - filled_amount;
+        let new_filled = filled_amount + fill_amount;
+        if fill_amount != expected_fill || new_filled > immutables.amount {
+            return Err(SwapError::FillExceedsAmount);
+        }
+
+        // Transfer the incremental fill to the taker
         let token_client = token::Client::new(&env, &immutables.token);
-        token_client.transfer(&env.current_contract_address(), &immutables.maker, &immutables.amount);
-        
+        token_client.transfer(&env.current_contract_address(), &immutables.taker, &fill_amount);
+
+        EscrowManager::set_filled(&env, &escrow_id, new_filled);
+        if new_filled == immutables.amount {
+            pay_safety_deposit(&env, &immutables, &caller);
+            EscrowManager::set_state(&env, &escrow_id, &EscrowState::Withdrawn);
+        }
+
+        // Emit event
+        env.events().publish(
+            (Symbol::new(&env, "escrow_partially_withdrawn"),),
+            (escrow_id, immutables.taker, fill_amount, secret_index)
+        );
+
+        Ok(true)
+    }
+
+    /// Cancel escrow and return funds to maker. In `PrivateCancel` only `maker`
+    /// may call; in `PublicCancel` anyone may, collecting the safety deposit.
+    pub fn cancel(env: Env, escrow_id: String, caller: Address) -> Result<bool, SwapError> {
+        caller.require_auth();
+
+        let immutables = EscrowManager::get(&env, &escrow_id).ok_or(SwapError::EscrowNotFound)?;
+        let state = EscrowManager::get_state(&env, &escrow_id).ok_or(SwapError::EscrowNotFound)?;
+
+        if state != EscrowState::Active {
+            return Err(SwapError::NotActive);
+        }
+
+        match immutables.timelock.phase_at(&env) {
+            Phase::PrivateCancel if caller != immutables.maker => return Err(SwapError::Unauthorized),
+            Phase::PrivateCancel | Phase::PublicCancel => {}
+            Phase::Finality | Phase::PrivateWithdraw | Phase::PublicWithdraw => return Err(SwapError::WrongPhase),
+        }
+
+        // Only the portion not already released via withdraw_partial is still
+        // escrowed for the maker to reclaim.
+        let filled_amount = EscrowManager::get_filled(&env, &escrow_id);
+        let remaining = immutables.amount - filled_amount;
+
+        // Transfer the unfilled remainder back to maker and the safety deposit to
+        // whoever resolved it
+        let token_client = token::Client::new(&env, &immutables.token);
+        token_client.transfer(&env.current_contract_address(), &immutables.maker, &remaining);
+        pay_safety_deposit(&env, &immutables, &caller);
+
         // Update state
-        env.storage().persistent().set(&DataKey::EscrowState(escrow_id.clone()), &EscrowState::Cancelled);
-        
+        if filled_amount > 0 {
+            EscrowManager::set_filled(&env, &escrow_id, 0);
+        }
+        EscrowManager::set_state(&env, &escrow_id, &EscrowState::Cancelled);
+
         // Emit event
         env.events().publish(
             (Symbol::new(&env, "escrow_cancelled"),),
-            (escrow_id, immutables.maker, immutables.amount)
+            (escrow_id, immutables.maker, remaining)
         );
-        
-        true
+
+        Ok(true)
     }
-    
+
     /// Get escrow details
-    pub fn get_escrow(env: Env, escrow_id: String) -> Option<Immutables> {
-        env.storage().persistent().get(&DataKey::Escrow(escrow_id))
+    pub fn get_escrow(env: Env, escrow_id: String) -> Result<Immutables, SwapError> {
+        EscrowManager::get(&env, &escrow_id).ok_or(SwapError::EscrowNotFound)
     }
-    
+
     /// Get escrow state
     pub fn get_escrow_state(env: Env, escrow_id: String) -> Option<EscrowState> {
-        env.storage().persistent().get(&DataKey::EscrowState(escrow_id))
+        EscrowManager::get_state(&env, &escrow_id)
     }
 }
 
-fn generate_escrow_id(env: &Env, _maker: &Address, _taker: &Address, _token: &Address, _amount: i128) -> String {
-    // Simple ID generation using timestamp
-    String::from_str(env, "escrow_")
+fn verify_hashlock(env: &Env, hashlock: &BytesN<32>, secret: &Bytes, chain_id: u32) -> bool {
+    &create_hashlock(env, secret, chain_id) == hashlock
 }
 
-fn verify_hashlock(_env: &Env, hashlock: &String, secret: &String) -> bool {
-    // Simple comparison for now - in production you'd want proper hashing
-    secret == hashlock
-}
\ No newline at end of file
+fn pay_safety_deposit(env: &Env, immutables: &Immutables, caller: &Address) {
+    if immutables.safety_deposit > 0 {
+        let token_client = token::Client::new(env, &immutables.token);
+        token_client.transfer(&env.current_contract_address(), caller, &immutables.safety_deposit);
+    }
+}
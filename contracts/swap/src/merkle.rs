@@ -0,0 +1,31 @@
+use soroban_sdk::{Bytes, BytesN, Env, Vec};
+
+/// Leaf for the partial-fill secrets tree: `hash(index ‖ hash(secret))`, so
+/// revealing a secret also commits to its position in the fill sequence.
+pub fn leaf_hash(env: &Env, index: u32, secret: &Bytes) -> BytesN<32> {
+    let secret_hash = env.crypto().sha256(secret).to_bytes();
+
+    let mut preimage = Bytes::from_array(env, &index.to_be_bytes());
+    preimage.append(&Bytes::from_array(env, &secret_hash.to_array()));
+    env.crypto().sha256(&preimage).to_bytes()
+}
+
+/// Fold `leaf` up through `proof` to the Merkle root, hashing each pair in
+/// sorted order so the proof doesn't need to encode left/right position.
+pub fn verify_proof(env: &Env, leaf: BytesN<32>, proof: &Vec<BytesN<32>>, root: &BytesN<32>) -> bool {
+    let mut computed = leaf;
+    for sibling in proof.iter() {
+        computed = hash_pair(env, &computed, &sibling);
+    }
+    &computed == root
+}
+
+/// Combine two tree nodes in sorted order. Exposed so off-chain tooling (and
+/// tests) can build a tree with the exact same rule `verify_proof` checks against.
+pub fn hash_pair(env: &Env, a: &BytesN<32>, b: &BytesN<32>) -> BytesN<32> {
+    let (first, second) = if a.to_array() <= b.to_array() { (a, b) } else { (b, a) };
+
+    let mut preimage = Bytes::from_array(env, &first.to_array());
+    preimage.append(&Bytes::from_array(env, &second.to_array()));
+    env.crypto().sha256(&preimage).to_bytes()
+}
@@ -1,13 +1,78 @@
+#[cfg(test)]
+extern crate std;
+
 #[cfg(test)]
 mod test {
-    use crate::{SwapContract, EscrowState, Immutables, DataKey};
-    use soroban_sdk::{testutils::{Address as _, Ledger as _}, Address, Env, String};
+    use crate::hashlock::create_hashlock;
+    use crate::{
+        EscrowManager, EscrowStore, SwapContract, SwapContractClient, EscrowState, Immutables,
+        DataKey, SwapError, Timelock,
+    };
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger as _},
+        token, Address, Bytes, BytesN, Env, String,
+    };
+    use std::cell::RefCell;
+
+    const CHAIN_ID: u32 = 1;
+    const OTHER_CHAIN_ID: u32 = 2;
+
+    // Durations (in ledger seconds) used to build a `Timelock` that starts
+    // `PrivateWithdraw` immediately (no finality wait) so most tests don't need
+    // to advance the ledger clock to exercise withdrawal/cancellation.
+    const FINALITY: u64 = 0;
+    const PRIVATE_WITHDRAW: u64 = 100;
+    const PUBLIC_WITHDRAW: u64 = 100;
+    const PRIVATE_CANCEL: u64 = 100;
+
+    /// In-memory `EscrowStore` used to unit test `EscrowManager` without touching
+    /// contract storage at all.
+    #[derive(Default)]
+    struct FakeStore {
+        escrows: RefCell<std::vec::Vec<(String, Immutables)>>,
+        states: RefCell<std::vec::Vec<(String, EscrowState)>>,
+    }
+
+    impl EscrowStore for FakeStore {
+        fn get_escrow(&self, id: &String) -> Option<Immutables> {
+            self.escrows.borrow().iter().find(|(k, _)| k == id).map(|(_, v)| v.clone())
+        }
+
+        fn set_escrow(&self, id: &String, immutables: &Immutables) {
+            self.escrows.borrow_mut().retain(|(k, _)| k != id);
+            self.escrows.borrow_mut().push((id.clone(), immutables.clone()));
+        }
+
+        fn get_state(&self, id: &String) -> Option<EscrowState> {
+            self.states.borrow().iter().find(|(k, _)| k == id).map(|(_, v)| v.clone())
+        }
+
+        fn set_state(&self, id: &String, state: &EscrowState) {
+            self.states.borrow_mut().retain(|(k, _)| k != id);
+            self.states.borrow_mut().push((id.clone(), state.clone()));
+        }
+
+        fn get_filled(&self, _id: &String) -> i128 {
+            0
+        }
+
+        fn set_filled(&self, _id: &String, _amount: i128) {}
+
+        fn bump_ttl(&self, _id: &String) {
+            // No ledger entries to expire off-chain.
+        }
+    }
+
+    fn create_token(env: &Env, admin: &Address) -> Address {
+        env.register_stellar_asset_contract_v2(admin.clone())
+            .address()
+    }
 
     #[test]
     fn test_basic_functionality() {
         let env = Env::default();
         let contract_id = env.register(SwapContract, ());
-        
+
         let maker = Address::generate(&env);
         let taker = Address::generate(&env);
         let token = Address::generate(&env);
@@ -21,24 +86,551 @@ mod test {
             taker: taker.clone(),
             token: token.clone(),
             amount: 1000i128,
-            hashlock: String::from_str(&env, "secret123"),
-            timelock_start: 1000u64,
-            timelock_end: 2000u64,
+            hashlock: create_hashlock(&env, &Bytes::from_slice(&env, b"secret123"), CHAIN_ID),
+            timelock: Timelock::new(env.ledger().timestamp(), FINALITY, PRIVATE_WITHDRAW, PUBLIC_WITHDRAW, PRIVATE_CANCEL),
+            chain_id: CHAIN_ID,
+            parts: 1,
+            safety_deposit: 0,
         };
 
         // Store test data using contract context
         env.as_contract(&contract_id, || {
             env.storage().persistent().set(&DataKey::Escrow(String::from_str(&env, "test_id")), &immutables);
             env.storage().persistent().set(&DataKey::EscrowState(String::from_str(&env, "test_id")), &EscrowState::Active);
-            
+
             // Retrieve and verify
             let stored_immutables: Option<Immutables> = env.storage().persistent().get(&DataKey::Escrow(String::from_str(&env, "test_id")));
             assert!(stored_immutables.is_some());
-            
+
             let data = stored_immutables.unwrap();
             assert_eq!(data.maker, maker);
             assert_eq!(data.taker, taker);
             assert_eq!(data.amount, 1000i128);
         });
     }
-}
\ No newline at end of file
+
+    /// Creates a 1000-unit, single-fill escrow with a `safety_deposit` of `100`,
+    /// funding `maker` and `taker` for both. Returns (client, escrow_id, secret, taker, token_address).
+    fn setup_escrow(env: &Env, contract_id: &Address) -> (SwapContractClient<'static>, String, Bytes, Address, Address) {
+        env.mock_all_auths();
+        let client = SwapContractClient::new(env, contract_id);
+
+        let admin = Address::generate(env);
+        let maker = Address::generate(env);
+        let taker = Address::generate(env);
+        let token_address = create_token(env, &admin);
+        token::StellarAssetClient::new(env, &token_address).mint(&maker, &1000i128);
+        token::StellarAssetClient::new(env, &token_address).mint(&taker, &100i128);
+
+        let secret = Bytes::from_slice(env, b"correct horse battery staple");
+        let hashlock = create_hashlock(env, &secret, CHAIN_ID);
+
+        let escrow_id = client.create_escrow(
+            &maker,
+            &taker,
+            &token_address,
+            &1000i128,
+            &hashlock,
+            &FINALITY,
+            &PRIVATE_WITHDRAW,
+            &PUBLIC_WITHDRAW,
+            &PRIVATE_CANCEL,
+            &CHAIN_ID,
+            &1u32,
+            &100i128,
+        );
+
+        (client, escrow_id, secret, taker, token_address)
+    }
+
+    #[test]
+    fn test_withdraw_with_correct_preimage_succeeds() {
+        let env = Env::default();
+        let contract_id = env.register(SwapContract, ());
+        let (client, escrow_id, secret, taker, token_address) = setup_escrow(&env, &contract_id);
+
+        client.withdraw(&escrow_id, &secret, &taker);
+
+        let token_client = token::Client::new(&env, &token_address);
+        // Taker collects both the swap amount and their own safety deposit back.
+        assert_eq!(token_client.balance(&taker), 1100i128);
+    }
+
+    #[test]
+    fn test_withdraw_with_wrong_preimage_fails() {
+        let env = Env::default();
+        let contract_id = env.register(SwapContract, ());
+        let (client, escrow_id, _secret, taker, _token_address) = setup_escrow(&env, &contract_id);
+
+        let result = client.try_withdraw(&escrow_id, &Bytes::from_slice(&env, b"wrong guess"), &taker);
+        assert_eq!(result, Err(Ok(SwapError::InvalidSecret)));
+    }
+
+    #[test]
+    fn test_withdraw_blocked_during_finality() {
+        let env = Env::default();
+        let contract_id = env.register(SwapContract, ());
+        env.mock_all_auths();
+        let client = SwapContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let maker = Address::generate(&env);
+        let taker = Address::generate(&env);
+        let token_address = create_token(&env, &admin);
+        token::StellarAssetClient::new(&env, &token_address).mint(&maker, &1000i128);
+        token::StellarAssetClient::new(&env, &token_address).mint(&taker, &100i128);
+
+        let secret = Bytes::from_slice(&env, b"finality guarded secret");
+        let hashlock = create_hashlock(&env, &secret, CHAIN_ID);
+
+        let escrow_id = client.create_escrow(
+            &maker,
+            &taker,
+            &token_address,
+            &1000i128,
+            &hashlock,
+            &50u64, // finality window open for 50s
+            &PRIVATE_WITHDRAW,
+            &PUBLIC_WITHDRAW,
+            &PRIVATE_CANCEL,
+            &CHAIN_ID,
+            &1u32,
+            &100i128,
+        );
+
+        let result = client.try_withdraw(&escrow_id, &secret, &taker);
+        assert_eq!(result, Err(Ok(SwapError::WrongPhase)));
+    }
+
+    #[test]
+    fn test_withdraw_by_non_taker_unauthorized_in_private_phase() {
+        let env = Env::default();
+        let contract_id = env.register(SwapContract, ());
+        let (client, escrow_id, secret, _taker, _token_address) = setup_escrow(&env, &contract_id);
+
+        let stranger = Address::generate(&env);
+        let result = client.try_withdraw(&escrow_id, &secret, &stranger);
+        assert_eq!(result, Err(Ok(SwapError::Unauthorized)));
+    }
+
+    #[test]
+    fn test_public_withdraw_pays_safety_deposit_to_resolver() {
+        let env = Env::default();
+        let contract_id = env.register(SwapContract, ());
+        let (client, escrow_id, secret, taker, token_address) = setup_escrow(&env, &contract_id);
+
+        // Advance past the private withdraw window into the public one.
+        env.ledger().set_timestamp(env.ledger().timestamp() + FINALITY + PRIVATE_WITHDRAW + 1);
+
+        let resolver = Address::generate(&env);
+        client.withdraw(&escrow_id, &secret, &resolver);
+
+        let token_client = token::Client::new(&env, &token_address);
+        assert_eq!(token_client.balance(&taker), 1000i128);
+        assert_eq!(token_client.balance(&resolver), 100i128);
+    }
+
+    #[test]
+    fn test_cancel_private_phase_only_maker() {
+        let env = Env::default();
+        let contract_id = env.register(SwapContract, ());
+        env.mock_all_auths();
+        let client = SwapContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let maker = Address::generate(&env);
+        let taker = Address::generate(&env);
+        let token_address = create_token(&env, &admin);
+        token::StellarAssetClient::new(&env, &token_address).mint(&maker, &1000i128);
+        token::StellarAssetClient::new(&env, &token_address).mint(&taker, &100i128);
+
+        let secret = Bytes::from_slice(&env, b"never revealed");
+        let hashlock = create_hashlock(&env, &secret, CHAIN_ID);
+
+        let escrow_id = client.create_escrow(
+            &maker,
+            &taker,
+            &token_address,
+            &1000i128,
+            &hashlock,
+            &FINALITY,
+            &PRIVATE_WITHDRAW,
+            &PUBLIC_WITHDRAW,
+            &PRIVATE_CANCEL,
+            &CHAIN_ID,
+            &1u32,
+            &100i128,
+        );
+
+        // Still in the withdrawal window - cancellation isn't allowed yet.
+        let result = client.try_cancel(&escrow_id, &maker);
+        assert_eq!(result, Err(Ok(SwapError::WrongPhase)));
+
+        // Move into the private cancel window.
+        env.ledger().set_timestamp(
+            env.ledger().timestamp() + FINALITY + PRIVATE_WITHDRAW + PUBLIC_WITHDRAW + 1,
+        );
+
+        let stranger = Address::generate(&env);
+        let result = client.try_cancel(&escrow_id, &stranger);
+        assert_eq!(result, Err(Ok(SwapError::Unauthorized)));
+
+        client.cancel(&escrow_id, &maker);
+
+        let token_client = token::Client::new(&env, &token_address);
+        // Maker reclaims the amount and, as the canceller, the safety deposit too.
+        assert_eq!(token_client.balance(&maker), 1100i128);
+    }
+
+    #[test]
+    fn test_public_cancel_pays_safety_deposit_to_resolver() {
+        let env = Env::default();
+        let contract_id = env.register(SwapContract, ());
+        env.mock_all_auths();
+        let client = SwapContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let maker = Address::generate(&env);
+        let taker = Address::generate(&env);
+        let token_address = create_token(&env, &admin);
+        token::StellarAssetClient::new(&env, &token_address).mint(&maker, &1000i128);
+        token::StellarAssetClient::new(&env, &token_address).mint(&taker, &100i128);
+
+        let secret = Bytes::from_slice(&env, b"abandoned swap");
+        let hashlock = create_hashlock(&env, &secret, CHAIN_ID);
+
+        let escrow_id = client.create_escrow(
+            &maker,
+            &taker,
+            &token_address,
+            &1000i128,
+            &hashlock,
+            &FINALITY,
+            &PRIVATE_WITHDRAW,
+            &PUBLIC_WITHDRAW,
+            &PRIVATE_CANCEL,
+            &CHAIN_ID,
+            &1u32,
+            &100i128,
+        );
+
+        // Move past the private cancel window into the public one.
+        env.ledger().set_timestamp(
+            env.ledger().timestamp()
+                + FINALITY
+                + PRIVATE_WITHDRAW
+                + PUBLIC_WITHDRAW
+                + PRIVATE_CANCEL
+                + 1,
+        );
+
+        let resolver = Address::generate(&env);
+        client.cancel(&escrow_id, &resolver);
+
+        let token_client = token::Client::new(&env, &token_address);
+        assert_eq!(token_client.balance(&maker), 1000i128);
+        assert_eq!(token_client.balance(&resolver), 100i128);
+    }
+
+    #[test]
+    fn test_escrow_manager_with_fake_store() {
+        let env = Env::default();
+        let store = FakeStore::default();
+
+        let immutables = Immutables {
+            maker: Address::generate(&env),
+            taker: Address::generate(&env),
+            token: Address::generate(&env),
+            amount: 500i128,
+            hashlock: create_hashlock(&env, &Bytes::from_slice(&env, b"fake-store-secret"), CHAIN_ID),
+            timelock: Timelock::new(env.ledger().timestamp(), FINALITY, PRIVATE_WITHDRAW, PUBLIC_WITHDRAW, PRIVATE_CANCEL),
+            chain_id: CHAIN_ID,
+            parts: 1,
+            safety_deposit: 0,
+        };
+
+        let escrow_id = EscrowManager::create(&env, &store, &immutables).unwrap();
+        assert_eq!(EscrowManager::get(&store, &escrow_id), Some(immutables.clone()));
+        assert_eq!(EscrowManager::get_state(&store, &escrow_id), Some(EscrowState::Active));
+
+        EscrowManager::set_state(&store, &escrow_id, &EscrowState::Withdrawn);
+        assert_eq!(EscrowManager::get_state(&store, &escrow_id), Some(EscrowState::Withdrawn));
+
+        // Recreating the exact same escrow (same derived id) must not silently
+        // overwrite the existing entry.
+        let result = EscrowManager::create(&env, &store, &immutables);
+        assert_eq!(result, Err(SwapError::EscrowAlreadyExists));
+    }
+
+    #[test]
+    fn test_withdraw_missing_escrow_returns_not_found() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(SwapContract, ());
+        let client = SwapContractClient::new(&env, &contract_id);
+
+        let caller = Address::generate(&env);
+        let secret = Bytes::from_slice(&env, b"anything");
+        let result = client.try_withdraw(&String::from_str(&env, "nope"), &secret, &caller);
+        assert_eq!(result, Err(Ok(SwapError::EscrowNotFound)));
+    }
+
+    #[test]
+    fn test_escrow_ids_differ_across_chain_ids() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(SwapContract, ());
+        let client = SwapContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let maker = Address::generate(&env);
+        let taker = Address::generate(&env);
+        let token_address = create_token(&env, &admin);
+        token::StellarAssetClient::new(&env, &token_address).mint(&maker, &2000i128);
+
+        let secret = Bytes::from_slice(&env, b"same secret, two chains");
+
+        let escrow_id_a = client.create_escrow(
+            &maker,
+            &taker,
+            &token_address,
+            &1000i128,
+            &create_hashlock(&env, &secret, CHAIN_ID),
+            &FINALITY,
+            &PRIVATE_WITHDRAW,
+            &PUBLIC_WITHDRAW,
+            &PRIVATE_CANCEL,
+            &CHAIN_ID,
+            &1u32,
+            &0i128,
+        );
+
+        let escrow_id_b = client.create_escrow(
+            &maker,
+            &taker,
+            &token_address,
+            &1000i128,
+            &create_hashlock(&env, &secret, OTHER_CHAIN_ID),
+            &FINALITY,
+            &PRIVATE_WITHDRAW,
+            &PUBLIC_WITHDRAW,
+            &PRIVATE_CANCEL,
+            &OTHER_CHAIN_ID,
+            &1u32,
+            &0i128,
+        );
+
+        assert_ne!(escrow_id_a, escrow_id_b);
+        client.get_escrow(&escrow_id_a);
+        client.get_escrow(&escrow_id_b);
+    }
+
+    #[test]
+    fn test_create_escrow_rejects_identical_duplicate() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(SwapContract, ());
+        let client = SwapContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let maker = Address::generate(&env);
+        let taker = Address::generate(&env);
+        let token_address = create_token(&env, &admin);
+        token::StellarAssetClient::new(&env, &token_address).mint(&maker, &2000i128);
+
+        let secret = Bytes::from_slice(&env, b"resubmitted order");
+        let hashlock = create_hashlock(&env, &secret, CHAIN_ID);
+
+        let args = (
+            maker.clone(),
+            taker.clone(),
+            token_address.clone(),
+            1000i128,
+            hashlock.clone(),
+            FINALITY,
+            PRIVATE_WITHDRAW,
+            PUBLIC_WITHDRAW,
+            PRIVATE_CANCEL,
+            CHAIN_ID,
+            1u32,
+            0i128,
+        );
+
+        client.create_escrow(
+            &args.0, &args.1, &args.2, &args.3, &args.4, &args.5, &args.6, &args.7, &args.8,
+            &args.9, &args.10, &args.11,
+        );
+
+        // A second, byte-for-byte identical order must not silently overwrite the
+        // first depositor's escrow.
+        let result = client.try_create_escrow(
+            &args.0, &args.1, &args.2, &args.3, &args.4, &args.5, &args.6, &args.7, &args.8,
+            &args.9, &args.10, &args.11,
+        );
+        assert_eq!(result, Err(Ok(SwapError::EscrowAlreadyExists)));
+    }
+
+    /// Builds the 3-leaf tree over secrets s_0, s_1, s_2 for a 2-part order and
+    /// returns (root, leaves, node01) so tests can assemble proofs by hand.
+    fn build_two_part_tree(env: &Env, secrets: &[Bytes; 3]) -> (BytesN<32>, [BytesN<32>; 3], BytesN<32>) {
+        let leaves: [BytesN<32>; 3] = [
+            crate::merkle::leaf_hash(env, 0, &secrets[0]),
+            crate::merkle::leaf_hash(env, 1, &secrets[1]),
+            crate::merkle::leaf_hash(env, 2, &secrets[2]),
+        ];
+        let node01 = crate::merkle::hash_pair(env, &leaves[0], &leaves[1]);
+        let root = crate::merkle::hash_pair(env, &node01, &leaves[2]);
+        (root, leaves, node01)
+    }
+
+    #[test]
+    fn test_partial_fill_two_steps_then_full() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(SwapContract, ());
+        let client = SwapContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let maker = Address::generate(&env);
+        let taker = Address::generate(&env);
+        let token_address = create_token(&env, &admin);
+        token::StellarAssetClient::new(&env, &token_address).mint(&maker, &1000i128);
+
+        let secrets = [
+            Bytes::from_slice(&env, b"secret-0"),
+            Bytes::from_slice(&env, b"secret-1"),
+            Bytes::from_slice(&env, b"secret-2"),
+        ];
+        let (root, leaves, node01) = build_two_part_tree(&env, &secrets);
+
+        let escrow_id = client.create_escrow(
+            &maker,
+            &taker,
+            &token_address,
+            &1000i128,
+            &root,
+            &FINALITY,
+            &PRIVATE_WITHDRAW,
+            &PUBLIC_WITHDRAW,
+            &PRIVATE_CANCEL,
+            &CHAIN_ID,
+            &2u32,
+            &0i128,
+        );
+
+        // 50%: reveal s_1, proof = [leaf0, leaf2]
+        let proof_50 = soroban_sdk::vec![&env, leaves[0].clone(), leaves[2].clone()];
+        client.withdraw_partial(&escrow_id, &500i128, &1u32, &secrets[1], &proof_50, &taker);
+
+        let token_client = token::Client::new(&env, &token_address);
+        assert_eq!(token_client.balance(&taker), 500i128);
+        assert_eq!(client.get_escrow_state(&escrow_id), Some(EscrowState::Active));
+
+        // 100%: reveal s_2, proof = [node01]
+        let proof_100 = soroban_sdk::vec![&env, node01.clone()];
+        client.withdraw_partial(&escrow_id, &500i128, &2u32, &secrets[2], &proof_100, &taker);
+
+        assert_eq!(token_client.balance(&taker), 1000i128);
+        assert_eq!(client.get_escrow_state(&escrow_id), Some(EscrowState::Withdrawn));
+    }
+
+    #[test]
+    fn test_partial_fill_out_of_order_proof_rejected() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(SwapContract, ());
+        let client = SwapContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let maker = Address::generate(&env);
+        let taker = Address::generate(&env);
+        let token_address = create_token(&env, &admin);
+        token::StellarAssetClient::new(&env, &token_address).mint(&maker, &1000i128);
+
+        let secrets = [
+            Bytes::from_slice(&env, b"secret-0"),
+            Bytes::from_slice(&env, b"secret-1"),
+            Bytes::from_slice(&env, b"secret-2"),
+        ];
+        let (root, leaves, node01) = build_two_part_tree(&env, &secrets);
+
+        let escrow_id = client.create_escrow(
+            &maker,
+            &taker,
+            &token_address,
+            &1000i128,
+            &root,
+            &FINALITY,
+            &PRIVATE_WITHDRAW,
+            &PUBLIC_WITHDRAW,
+            &PRIVATE_CANCEL,
+            &CHAIN_ID,
+            &2u32,
+            &0i128,
+        );
+
+        // Reveal index 1 (50%) once; replaying it should be rejected as out-of-order.
+        let proof_50 = soroban_sdk::vec![&env, leaves[0].clone(), leaves[2].clone()];
+        client.withdraw_partial(&escrow_id, &500i128, &1u32, &secrets[1], &proof_50, &taker);
+
+        let result = client.try_withdraw_partial(&escrow_id, &500i128, &1u32, &secrets[1], &proof_50, &taker);
+        assert_eq!(result, Err(Ok(SwapError::FillOutOfOrder)));
+
+        // A bogus proof for a legitimate index is rejected too.
+        let bogus_proof = soroban_sdk::vec![&env, node01.clone(), leaves[2].clone()];
+        let result = client.try_withdraw_partial(&escrow_id, &500i128, &2u32, &secrets[2], &bogus_proof, &taker);
+        assert_eq!(result, Err(Ok(SwapError::InvalidMerkleProof)));
+    }
+
+    #[test]
+    fn test_cancel_after_partial_fill_returns_only_unfilled_remainder() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(SwapContract, ());
+        let client = SwapContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let maker = Address::generate(&env);
+        let taker = Address::generate(&env);
+        let token_address = create_token(&env, &admin);
+        token::StellarAssetClient::new(&env, &token_address).mint(&maker, &1000i128);
+
+        let secrets = [
+            Bytes::from_slice(&env, b"secret-0"),
+            Bytes::from_slice(&env, b"secret-1"),
+            Bytes::from_slice(&env, b"secret-2"),
+        ];
+        let (root, leaves, _node01) = build_two_part_tree(&env, &secrets);
+
+        let escrow_id = client.create_escrow(
+            &maker,
+            &taker,
+            &token_address,
+            &1000i128,
+            &root,
+            &FINALITY,
+            &PRIVATE_WITHDRAW,
+            &PUBLIC_WITHDRAW,
+            &PRIVATE_CANCEL,
+            &CHAIN_ID,
+            &2u32,
+            &0i128,
+        );
+
+        // Taker claims 50% before the maker gives up and cancels.
+        let proof_50 = soroban_sdk::vec![&env, leaves[0].clone(), leaves[2].clone()];
+        client.withdraw_partial(&escrow_id, &500i128, &1u32, &secrets[1], &proof_50, &taker);
+
+        // Move into the private cancel window.
+        env.ledger().set_timestamp(
+            env.ledger().timestamp() + FINALITY + PRIVATE_WITHDRAW + PUBLIC_WITHDRAW + 1,
+        );
+        client.cancel(&escrow_id, &maker);
+
+        let token_client = token::Client::new(&env, &token_address);
+        // Taker keeps the 500 already released; the contract must not also pay the
+        // maker the full original amount out of other escrows' pooled balance.
+        assert_eq!(token_client.balance(&taker), 500i128);
+        assert_eq!(token_client.balance(&maker), 500i128);
+    }
+}
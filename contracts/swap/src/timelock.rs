@@ -1,29 +1,82 @@
 use soroban_sdk::{contracttype, Env};
 
+/// Ordered stage of an escrow's lifecycle. Each stage starts when the previous
+/// one's duration elapses, measured from `Timelock::deployed_at`:
+///
+/// `Finality -> PrivateWithdraw -> PublicWithdraw -> PrivateCancel -> PublicCancel`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Phase {
+    /// Neither withdrawal nor cancellation is allowed yet, giving the source
+    /// chain time to finalize before funds can move on this one.
+    Finality,
+    /// Only `taker` may withdraw with the secret.
+    PrivateWithdraw,
+    /// Anyone may submit the secret to complete the withdrawal on the taker's
+    /// behalf, collecting the safety deposit for doing so.
+    PublicWithdraw,
+    /// Only `maker` may cancel and reclaim the funds.
+    PrivateCancel,
+    /// Anyone may cancel on the maker's behalf, collecting the safety deposit.
+    PublicCancel,
+}
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Timelock {
-    pub start: u64,
-    pub end: u64,
+    pub deployed_at: u64,
+    pub finality_duration: u64,
+    pub private_withdraw_duration: u64,
+    pub public_withdraw_duration: u64,
+    pub private_cancel_duration: u64,
 }
 
 impl Timelock {
-    pub fn new(start: u64, end: u64) -> Self {
-        Self { start, end }
+    pub fn new(
+        deployed_at: u64,
+        finality_duration: u64,
+        private_withdraw_duration: u64,
+        public_withdraw_duration: u64,
+        private_cancel_duration: u64,
+    ) -> Self {
+        Self {
+            deployed_at,
+            finality_duration,
+            private_withdraw_duration,
+            public_withdraw_duration,
+            private_cancel_duration,
+        }
+    }
+
+    fn private_withdraw_start(&self) -> u64 {
+        self.deployed_at + self.finality_duration
     }
-    
-    pub fn is_active(&self, env: &Env) -> bool {
-        let current_time = env.ledger().timestamp();
-        current_time >= self.start && current_time <= self.end
+
+    fn public_withdraw_start(&self) -> u64 {
+        self.private_withdraw_start() + self.private_withdraw_duration
+    }
+
+    fn private_cancel_start(&self) -> u64 {
+        self.public_withdraw_start() + self.public_withdraw_duration
     }
-    
-    pub fn is_expired(&self, env: &Env) -> bool {
-        let current_time = env.ledger().timestamp();
-        current_time > self.end
+
+    fn public_cancel_start(&self) -> u64 {
+        self.private_cancel_start() + self.private_cancel_duration
     }
-    
-    pub fn is_before_start(&self, env: &Env) -> bool {
-        let current_time = env.ledger().timestamp();
-        current_time < self.start
+
+    /// Which stage of the escrow's lifecycle the current ledger time falls into.
+    pub fn phase_at(&self, env: &Env) -> Phase {
+        let now = env.ledger().timestamp();
+        if now < self.private_withdraw_start() {
+            Phase::Finality
+        } else if now < self.public_withdraw_start() {
+            Phase::PrivateWithdraw
+        } else if now < self.private_cancel_start() {
+            Phase::PublicWithdraw
+        } else if now < self.public_cancel_start() {
+            Phase::PrivateCancel
+        } else {
+            Phase::PublicCancel
+        }
     }
-}
\ No newline at end of file
+}
@@ -1,4 +1,5 @@
-use soroban_sdk::{contracttype, Address, String};
+use soroban_sdk::{contracttype, Address, BytesN, String};
+use crate::timelock::Timelock;
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -7,9 +8,23 @@ pub struct Immutables {
     pub taker: Address,
     pub token: Address,
     pub amount: i128,
-    pub hashlock: String,
-    pub timelock_start: u64,
-    pub timelock_end: u64,
+    pub hashlock: BytesN<32>,
+    pub timelock: Timelock,
+    /// Domain tag for the network this escrow is meant to settle on, folded into
+    /// the escrow id and, for single-fill escrows, the hashlock digest so a secret
+    /// or id can't be replayed against an identically-parameterized escrow on
+    /// another chain. The Merkle leaves of a partial-fill escrow (`parts > 1`)
+    /// hash only the index and secret, not `chain_id`.
+    pub chain_id: u32,
+    /// Number of fill segments the order is split into. `1` means `hashlock` is a
+    /// plain secret digest settled in one shot via `withdraw`; `> 1` means
+    /// `hashlock` is the root of a Merkle tree of `parts + 1` ordered secrets
+    /// settled incrementally via `withdraw_partial`.
+    pub parts: u32,
+    /// Deposit the taker posts on creation, paid out to whoever triggers the
+    /// completing withdrawal or cancellation as an incentive to resolve a
+    /// stalled swap once its public phase opens.
+    pub safety_deposit: i128,
 }
 
 #[contracttype]
@@ -25,6 +40,7 @@ pub enum EscrowState {
 pub enum DataKey {
     Escrow(String),
     EscrowState(String),
+    EscrowFilled(String),
 }
 
 #[contracttype]
@@ -37,4 +53,5 @@ pub struct Order {
     pub taker_asset: Address,
     pub making_amount: i128,
     pub taking_amount: i128,
+    pub chain_id: u32,
 }
\ No newline at end of file